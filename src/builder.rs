@@ -111,19 +111,40 @@ where
 
     fn node_builder_to_node(node_builder: Rc<NodeBuilder<E, W, V>>) -> Node<E, W, V> {
         let node_builder = Rc::try_unwrap(node_builder).map_err(|_| ()).unwrap();
-        let key_part = node_builder.key_part;
-        let value = node_builder.value.into_inner();
 
-        let children = node_builder.children.into_inner().map(|children| {
+        let mut key_parts = match node_builder.key_part {
+            Some(key_part) => vec![key_part],
+            None => Vec::new(),
+        };
+        let mut value = node_builder.value.into_inner();
+        let mut children = node_builder.children.into_inner().map(|children| {
+            // Children are converted bottom-up first, so any run below them
+            // has already been compacted by the time we consider merging
+            // them into `self`.
             children
                 .into_sorted_vec()
                 .into_iter()
                 .map(Self::node_builder_to_node)
-                .collect()
+                .collect::<Vec<_>>()
         });
 
+        // Collapse any chain of single-child, valueless nodes into this edge,
+        // like a radix tree. A node carrying a value is a valid terminal key
+        // and must never be merged away.
+        while value.is_none() {
+            let Some(node_children) = &children else { break };
+            if node_children.len() != 1 {
+                break;
+            }
+
+            let mut child = children.take().unwrap().remove(0);
+            key_parts.append(&mut child.key_parts);
+            value = child.value;
+            children = child.children;
+        }
+
         Node {
-            key_part,
+            key_parts,
             value,
             children,
         }
@@ -190,3 +211,49 @@ where
         self.key_part.cmp(&other.key_part)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_entry_merges_root_into_its_only_child() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a"], 1);
+        let map = builder.build();
+
+        assert_eq!(map.get(["a"]), Some(&1));
+    }
+
+    #[test]
+    fn shared_first_key_part_merges_root_into_its_only_child() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a", "b"], 1);
+        let map = builder.build();
+
+        assert_eq!(map.get(["a", "b"]), Some(&1));
+        assert_eq!(map.get(["a"]), None);
+    }
+
+    #[test]
+    fn top_level_branch_keeps_each_branch_reachable() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a"], 1);
+        builder.insert_exact(["b"], 2);
+        let map = builder.build();
+
+        assert_eq!(map.get(["a"]), Some(&1));
+        assert_eq!(map.get(["b"]), Some(&2));
+    }
+
+    #[test]
+    fn value_at_an_intermediate_node_blocks_further_compaction() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a"], 1);
+        builder.insert_exact(["a", "b"], 2);
+        let map = builder.build();
+
+        assert_eq!(map.get(["a"]), Some(&1));
+        assert_eq!(map.get(["a", "b"]), Some(&2));
+    }
+}
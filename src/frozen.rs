@@ -0,0 +1,330 @@
+//! Zero-copy, memory-mappable serialized form of a built [`PrefixTreeMap`]
+
+use {
+    crate::{
+        key_part::KeyPart,
+        prefix_tree_map::{Node, PrefixTreeMap},
+    },
+    core::marker::PhantomData,
+};
+
+/// Encodes a value into, and decodes it back out of, a flat byte buffer
+///
+/// Implemented for key and value types so [`PrefixTreeMap::freeze`] can lay
+/// them out in the frozen arena, and [`FrozenPrefixTreeMap`] can read them
+/// back directly from bytes.
+pub trait ToBytes: Sized {
+    /// Appends the encoded form of `self` to `out`
+    fn to_bytes(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value previously written by [`to_bytes`](Self::to_bytes)
+    ///
+    /// `bytes` is exactly the span written by the matching `to_bytes` call.
+    fn from_bytes(bytes: &[u8]) -> Self;
+}
+
+impl ToBytes for Vec<u8> {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        bytes.to_vec()
+    }
+}
+
+impl ToBytes for String {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// A node record laid out in the frozen arena
+///
+/// ```text
+/// key_parts_len: u32
+/// key_parts: [ tag: u8, len: u32, bytes: [u8; len] ] * key_parts_len
+/// has_value: u8
+/// value:     [ len: u32, bytes: [u8; len] ]          (only if has_value != 0)
+/// children_len: u32
+/// children_offsets: [u32; children_len]
+/// ```
+const TAG_EXACT: u8 = 0;
+const TAG_WILDCARD: u8 = 1;
+
+fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(
+        bytes[offset..offset + 4]
+            .try_into()
+            .expect("slice is exactly 4 bytes"),
+    )
+}
+
+fn write_bytes_field(out: &mut Vec<u8>, bytes: impl FnOnce(&mut Vec<u8>)) {
+    let len_offset = out.len();
+    write_u32(out, 0);
+    let start = out.len();
+    bytes(out);
+    let len = (out.len() - start) as u32;
+    out[len_offset..start].copy_from_slice(&len.to_le_bytes());
+}
+
+/// Appends the node, and its subtree, to `out` in post-order, so that every
+/// child is written (and its offset known) before its parent
+fn encode_node<E, W, V>(node: &Node<E, W, V>, out: &mut Vec<u8>) -> u32
+where
+    E: ToBytes,
+    W: ToBytes,
+    V: ToBytes,
+{
+    let child_offsets = node
+        .children
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(|child| encode_node(child, out))
+        .collect::<Vec<_>>();
+
+    let offset = out.len() as u32;
+
+    write_u32(out, node.key_parts.len() as u32);
+    for key_part in &node.key_parts {
+        match key_part {
+            KeyPart::Exact(exact) => {
+                out.push(TAG_EXACT);
+                write_bytes_field(out, |out| exact.to_bytes(out));
+            },
+            KeyPart::Wildcard(wildcard) => {
+                out.push(TAG_WILDCARD);
+                write_bytes_field(out, |out| wildcard.to_bytes(out));
+            },
+        }
+    }
+
+    match &node.value {
+        Some(value) => {
+            out.push(1);
+            write_bytes_field(out, |out| value.to_bytes(out));
+        },
+        None => out.push(0),
+    }
+
+    write_u32(out, child_offsets.len() as u32);
+    for child_offset in child_offsets {
+        write_u32(out, child_offset);
+    }
+
+    offset
+}
+
+impl<E, W, V> PrefixTreeMap<E, W, V>
+where
+    E: ToBytes,
+    W: ToBytes,
+    V: ToBytes,
+{
+    /// Serializes this map into a single flat, append-only byte buffer
+    ///
+    /// The result can be written to disk and later read back with
+    /// [`FrozenPrefixTreeMap::from_bytes`], including from a memory-mapped
+    /// file, without rebuilding the [`Node`] tree.
+    pub fn freeze(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let root_offset = encode_node(&self.root, &mut out);
+
+        write_u32(&mut out, root_offset);
+        write_u32(&mut out, self.max_wildcard_depth as u32);
+
+        out
+    }
+}
+
+/// Marks the key/value types a [`FrozenPrefixTreeMap`] decodes into, without
+/// owning any of them
+type KeyValueMarker<E, W, V> = PhantomData<fn() -> (E, W, V)>;
+
+/// A borrowed, read-only view over a [`PrefixTreeMap`] frozen by [`PrefixTreeMap::freeze`]
+///
+/// Reads nodes directly out of `bytes` by following offsets, so a large map
+/// can be loaded instantly (e.g. via `mmap`) and shared read-only across
+/// processes, without rebuilding the [`Node`] tree in memory.
+pub struct FrozenPrefixTreeMap<'a, E, W, V> {
+    bytes: &'a [u8],
+    root_offset: usize,
+    max_wildcard_depth: usize,
+    _key_value: KeyValueMarker<E, W, V>,
+}
+
+impl<'a, E, W, V> FrozenPrefixTreeMap<'a, E, W, V> {
+    /// Reads a map previously written by [`PrefixTreeMap::freeze`]
+    ///
+    /// This only reads the trailing footer; the node arena itself is read
+    /// lazily, node-by-node, as [`get`](Self::get) walks it.
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        let max_wildcard_depth = read_u32(bytes, bytes.len() - 4) as usize;
+        let root_offset = read_u32(bytes, bytes.len() - 8) as usize;
+
+        Self {
+            bytes,
+            root_offset,
+            max_wildcard_depth,
+            _key_value: PhantomData,
+        }
+    }
+
+    /// Returns the maximum number of wildcard key parts along any inserted key
+    pub fn max_wildcard_depth(&self) -> usize {
+        self.max_wildcard_depth
+    }
+}
+
+/// A single key part read out of the frozen arena
+enum FrozenKeyPart<'a> {
+    Exact(&'a [u8]),
+    Wildcard,
+}
+
+/// Reads the `key_parts_len` key parts starting at `offset`, returning the
+/// offset of the byte immediately following them
+fn read_key_parts(bytes: &[u8], offset: usize) -> (Vec<FrozenKeyPart<'_>>, usize) {
+    let len = read_u32(bytes, offset) as usize;
+    let mut offset = offset + 4;
+
+    let mut key_parts = Vec::with_capacity(len);
+    for _ in 0..len {
+        let tag = bytes[offset];
+        offset += 1;
+
+        let field_len = read_u32(bytes, offset) as usize;
+        offset += 4;
+
+        let field = &bytes[offset..offset + field_len];
+        offset += field_len;
+
+        key_parts.push(match tag {
+            TAG_EXACT => FrozenKeyPart::Exact(field),
+            TAG_WILDCARD => FrozenKeyPart::Wildcard,
+            _ => unreachable!("frozen arena is well-formed"),
+        });
+    }
+
+    (key_parts, offset)
+}
+
+impl<'a, E, W, V> FrozenPrefixTreeMap<'a, E, W, V>
+where
+    E: ToBytes + PartialEq,
+    V: ToBytes,
+{
+    /// Returns the value associated with `key`, if any
+    ///
+    /// Wildcard key parts in the tree match any element of `key`. Decodes
+    /// only the key parts and value it actually visits.
+    pub fn get(&self, key: impl IntoIterator<Item = E>) -> Option<V> {
+        let mut key = key.into_iter().peekable();
+        let mut offset = self.root_offset;
+
+        loop {
+            let (key_parts, mut cursor) = read_key_parts(self.bytes, offset);
+
+            for key_part in key_parts {
+                match key_part {
+                    FrozenKeyPart::Exact(bytes) => {
+                        let next = key.next()?;
+                        if E::from_bytes(bytes) != next {
+                            return None;
+                        }
+                    },
+                    FrozenKeyPart::Wildcard => {
+                        key.next()?;
+                    },
+                }
+            }
+
+            let has_value = self.bytes[cursor] != 0;
+            cursor += 1;
+
+            let value = has_value.then(|| {
+                let len = read_u32(self.bytes, cursor) as usize;
+                cursor += 4;
+                let value = V::from_bytes(&self.bytes[cursor..cursor + len]);
+                cursor += len;
+                value
+            });
+
+            let children_len = read_u32(self.bytes, cursor) as usize;
+            cursor += 4;
+            let children_offsets = (0..children_len)
+                .map(|i| read_u32(self.bytes, cursor + i * 4) as usize)
+                .collect::<Vec<_>>();
+
+            if key.peek().is_none() {
+                return value;
+            }
+
+            let next = key.peek().expect("just checked this is `Some`");
+
+            let mut matched = None;
+            for &child_offset in &children_offsets {
+                let (child_key_parts, _) = read_key_parts(self.bytes, child_offset);
+                match child_key_parts.first() {
+                    Some(FrozenKeyPart::Exact(bytes)) if &E::from_bytes(bytes) == next => {
+                        matched = Some(child_offset);
+                        break;
+                    },
+                    _ => {},
+                }
+            }
+            if matched.is_none() {
+                matched = children_offsets.iter().copied().find(|&child_offset| {
+                    let (child_key_parts, _) = read_key_parts(self.bytes, child_offset);
+                    matches!(child_key_parts.first(), Some(FrozenKeyPart::Wildcard))
+                });
+            }
+
+            offset = matched?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder::PrefixTreeMapBuilder, frozen::FrozenPrefixTreeMap, key_part::KeyPart};
+
+    #[test]
+    fn round_trips_an_exact_key() {
+        let mut builder = PrefixTreeMapBuilder::<String, String, String>::new();
+        builder.insert_exact(["a".to_string(), "b".to_string()], "value".to_string());
+        let map = builder.build();
+
+        let bytes = map.freeze();
+        let frozen = FrozenPrefixTreeMap::<String, String, String>::from_bytes(&bytes);
+
+        assert_eq!(
+            frozen.get(["a".to_string(), "b".to_string()]),
+            Some("value".to_string())
+        );
+        assert_eq!(frozen.get(["a".to_string()]), None);
+    }
+
+    #[test]
+    fn round_trips_a_wildcard_match() {
+        let mut builder = PrefixTreeMapBuilder::<String, String, String>::new();
+        builder.insert([KeyPart::Wildcard("w".to_string())], "value".to_string());
+        let map = builder.build();
+
+        let bytes = map.freeze();
+        let frozen = FrozenPrefixTreeMap::<String, String, String>::from_bytes(&bytes);
+
+        assert_eq!(frozen.get(["anything".to_string()]), Some("value".to_string()));
+        assert_eq!(frozen.max_wildcard_depth(), 1);
+    }
+}
@@ -0,0 +1,52 @@
+//! Key parts
+
+use crate::std_lib::Ordering;
+
+/// A single part of a key
+///
+/// A key inserted into a [`PrefixTreeMapBuilder`](crate::PrefixTreeMapBuilder)
+/// is a sequence of these, each either matching a concrete element exactly,
+/// or matching any element via a wildcard.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyPart<E, W> {
+    /// Matches an element exactly
+    Exact(E),
+
+    /// Matches any element
+    Wildcard(W),
+}
+
+impl<E, W> KeyPart<E, W> {
+    /// Returns whether this key part is a wildcard
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self, Self::Wildcard(_))
+    }
+}
+
+impl<E, W> PartialOrd for KeyPart<E, W>
+where
+    E: Ord,
+    W: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<E, W> Ord for KeyPart<E, W>
+where
+    E: Ord,
+    W: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Exact(lhs), Self::Exact(rhs)) => lhs.cmp(rhs),
+            (Self::Wildcard(lhs), Self::Wildcard(rhs)) => lhs.cmp(rhs),
+
+            // Exact key parts sort before wildcards, so children are visited
+            // in exact-first order during lookup.
+            (Self::Exact(_), Self::Wildcard(_)) => Ordering::Less,
+            (Self::Wildcard(_), Self::Exact(_)) => Ordering::Greater,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! A prefix tree (trie) map, supporting wildcard key parts.
+
+mod builder;
+mod frozen;
+mod key_part;
+mod prefix_tree_map;
+mod small_bytes;
+mod std_lib;
+
+pub use self::{
+    builder::PrefixTreeMapBuilder,
+    frozen::{FrozenPrefixTreeMap, ToBytes},
+    key_part::KeyPart,
+    prefix_tree_map::{AmbiguousKey, PrefixTreeMap},
+    small_bytes::SmallBytes,
+};
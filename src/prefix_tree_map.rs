@@ -0,0 +1,490 @@
+//! The built prefix tree map
+
+use {crate::key_part::KeyPart, core::fmt};
+
+/// A node of the built tree
+///
+/// The edge leading to a node may carry a run of several key parts rather
+/// than a single one: any chain of nodes with no stored value and exactly
+/// one child is collapsed into its child during [`build`](crate::PrefixTreeMapBuilder::build),
+/// so a `key_parts` run must be matched in full, or not at all.
+#[derive(Clone)]
+pub(crate) struct Node<E, W, V> {
+    pub(crate) key_parts: Vec<KeyPart<E, W>>,
+    pub(crate) value: Option<V>,
+    pub(crate) children: Option<Vec<Node<E, W, V>>>,
+}
+
+/// The key matched more than one stored pattern
+///
+/// Only possible when the tree contains wildcards: e.g. both `a/*` and
+/// `*/b` match the key `a/b`. Returned by [`PrefixTreeMap::get_unique`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmbiguousKey;
+
+impl fmt::Display for AmbiguousKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key matched more than one stored pattern")
+    }
+}
+
+impl std::error::Error for AmbiguousKey {}
+
+/// A prefix tree map
+///
+/// Supports wildcard key parts. Built using a
+/// [`PrefixTreeMapBuilder`](crate::PrefixTreeMapBuilder).
+pub struct PrefixTreeMap<E, W, V> {
+    pub(crate) root: Node<E, W, V>,
+    pub(crate) max_wildcard_depth: usize,
+}
+
+impl<E, W, V> PrefixTreeMap<E, W, V>
+where
+    E: PartialEq,
+{
+    /// Returns the value associated with `key`, if any
+    ///
+    /// Wildcard key parts in the tree match any element of `key`.
+    pub fn get(&self, key: impl IntoIterator<Item = E>) -> Option<&V> {
+        let key = key.into_iter().collect::<Vec<_>>();
+        let mut node = &self.root;
+        let mut key_pos = 0;
+
+        loop {
+            // Consume `node`'s own run first: compaction can merge the root
+            // itself into its only child, so even the starting node may
+            // carry real key parts that still need matching.
+            (key_pos, _) = Self::consume_edge(node, &key, key_pos, usize::MAX)?;
+
+            if key_pos == key.len() {
+                return node.value.as_ref();
+            }
+
+            let children = node.children.as_ref()?;
+            let next = &key[key_pos];
+
+            node = children
+                .iter()
+                .find(|child| matches!(child.key_parts.first(), Some(KeyPart::Exact(e)) if e == next))
+                .or_else(|| {
+                    children
+                        .iter()
+                        .find(|child| matches!(child.key_parts.first(), Some(KeyPart::Wildcard(_))))
+                })?;
+        }
+    }
+
+    /// Returns every value stored along the path matched by `key`, in root-to-leaf order
+    ///
+    /// When both an exact and a wildcard child match a key part, both
+    /// branches are followed (bounded by the tree's maximum wildcard depth),
+    /// with values from exact matches ranked before values from wildcard
+    /// matches at the same depth.
+    pub fn find_prefixes(&self, key: impl IntoIterator<Item = E>) -> Vec<&V> {
+        let key = key.into_iter().collect::<Vec<_>>();
+        let mut entries = Vec::new();
+
+        Self::visit_prefixes(&self.root, &key, 0, self.max_wildcard_depth, &mut entries);
+
+        entries.into_iter().map(|(_depth, value)| value).collect()
+    }
+
+    /// Returns the value of the deepest node matched by `key`, if any
+    ///
+    /// "Deepest" is measured by how many key parts were consumed to reach
+    /// it, not by the root-to-leaf, exact-before-wildcard visitation order
+    /// used by [`find_prefixes`](Self::find_prefixes): sibling wildcard
+    /// branches can match to different depths, and the deepest one wins
+    /// regardless of which branch was visited last. Ties in depth are still
+    /// broken in `find_prefixes`'s exact-before-wildcard order.
+    pub fn find_longest_prefix(&self, key: impl IntoIterator<Item = E>) -> Option<&V> {
+        let key = key.into_iter().collect::<Vec<_>>();
+        let mut entries = Vec::new();
+
+        Self::visit_prefixes(&self.root, &key, 0, self.max_wildcard_depth, &mut entries);
+
+        entries
+            .into_iter()
+            .fold(None, |deepest: Option<(usize, &V)>, (depth, value)| match deepest {
+                Some((deepest_depth, _)) if deepest_depth >= depth => deepest,
+                _ => Some((depth, value)),
+            })
+            .map(|(_depth, value)| value)
+    }
+
+    /// Consumes `node`'s key-part run against `key` starting at `key_pos`
+    ///
+    /// Returns the updated `(key_pos, wildcard_budget)` if the whole run
+    /// matches, or `None` if it doesn't (or the wildcard budget runs out).
+    /// Shared by the branching traversals below ([`visit_prefixes`](Self::visit_prefixes),
+    /// [`visit_unique`](Self::visit_unique)), which only differ in how a
+    /// match is recorded.
+    fn consume_edge(
+        node: &Node<E, W, V>,
+        key: &[E],
+        mut key_pos: usize,
+        mut wildcard_budget: usize,
+    ) -> Option<(usize, usize)> {
+        for key_part in &node.key_parts {
+            match key_part {
+                KeyPart::Exact(e) => {
+                    if key.get(key_pos) != Some(e) {
+                        return None;
+                    }
+                    key_pos += 1;
+                },
+                KeyPart::Wildcard(_) => {
+                    if key_pos >= key.len() {
+                        return None;
+                    }
+
+                    wildcard_budget = wildcard_budget.checked_sub(1)?;
+                    key_pos += 1;
+                },
+            }
+        }
+
+        Some((key_pos, wildcard_budget))
+    }
+
+    /// Walks every path through `node` consistent with `key`, pushing the
+    /// `(depth, value)` of each visited node that has a value, in
+    /// root-to-leaf, exact-before-wildcard order
+    ///
+    /// `depth` is the number of key parts consumed to reach that node, so
+    /// callers that care about the deepest match (like
+    /// [`find_longest_prefix`](Self::find_longest_prefix)) don't have to
+    /// rely on visitation order.
+    fn visit_prefixes<'node>(
+        node: &'node Node<E, W, V>,
+        key: &[E],
+        key_pos: usize,
+        wildcard_budget: usize,
+        entries: &mut Vec<(usize, &'node V)>,
+    ) {
+        let Some((key_pos, wildcard_budget)) = Self::consume_edge(node, key, key_pos, wildcard_budget) else {
+            return;
+        };
+
+        if let Some(value) = &node.value {
+            entries.push((key_pos, value));
+        }
+
+        if key_pos == key.len() {
+            return;
+        }
+
+        let Some(children) = &node.children else { return };
+
+        for child in children {
+            if matches!(child.key_parts.first(), Some(KeyPart::Exact(e)) if Some(e) == key.get(key_pos))
+            {
+                Self::visit_prefixes(child, key, key_pos, wildcard_budget, entries);
+            }
+        }
+        for child in children {
+            if matches!(child.key_parts.first(), Some(KeyPart::Wildcard(_))) {
+                Self::visit_prefixes(child, key, key_pos, wildcard_budget, entries);
+            }
+        }
+    }
+
+    /// Returns every value stored under `prefix`, i.e. reachable by
+    /// appending zero or more key parts to it
+    ///
+    /// Useful for autocomplete-style lookups: all completions of a typed prefix.
+    pub fn find_postfixes(&self, prefix: impl IntoIterator<Item = E>) -> Vec<&V> {
+        let prefix = prefix.into_iter().collect::<Vec<_>>();
+        let mut values = Vec::new();
+
+        Self::visit_postfixes(&self.root, &prefix, 0, &mut values);
+
+        values
+    }
+
+    /// Walks every path through `node` consistent with `prefix`, collecting
+    /// the values of every node reachable once `prefix` is fully consumed
+    ///
+    /// Like [`visit_prefixes`](Self::visit_prefixes), both an exact and a
+    /// wildcard child are followed when they both match, so a stored pattern
+    /// reachable only through a wildcard sibling isn't dropped.
+    fn visit_postfixes<'node>(
+        node: &'node Node<E, W, V>,
+        prefix: &[E],
+        mut prefix_pos: usize,
+        values: &mut Vec<&'node V>,
+    ) {
+        for key_part in &node.key_parts {
+            let Some(next) = prefix.get(prefix_pos) else {
+                // The prefix ran out partway through this edge: everything
+                // from here down, including this node, is under it.
+                Self::collect_subtree(node, values);
+                return;
+            };
+
+            match key_part {
+                KeyPart::Exact(e) if e == next => prefix_pos += 1,
+                KeyPart::Wildcard(_) => prefix_pos += 1,
+                KeyPart::Exact(_) => return,
+            }
+        }
+
+        if prefix_pos == prefix.len() {
+            Self::collect_subtree(node, values);
+            return;
+        }
+
+        let Some(children) = &node.children else { return };
+
+        for child in children {
+            if matches!(child.key_parts.first(), Some(KeyPart::Exact(e)) if Some(e) == prefix.get(prefix_pos))
+            {
+                Self::visit_postfixes(child, prefix, prefix_pos, values);
+            }
+        }
+        for child in children {
+            if matches!(child.key_parts.first(), Some(KeyPart::Wildcard(_))) {
+                Self::visit_postfixes(child, prefix, prefix_pos, values);
+            }
+        }
+    }
+
+    /// Returns an iterator over every key and value stored in the map
+    ///
+    /// Keys are reconstructed from the (possibly compacted) edges leading to
+    /// each value, in the same root-to-leaf, children-sorted order used by
+    /// [`find_postfixes`](Self::find_postfixes).
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<KeyPart<E, W>>, &V)>
+    where
+        E: Clone,
+        W: Clone,
+    {
+        let mut entries = Vec::new();
+        Self::collect_entries(&self.root, Vec::new(), &mut entries);
+        entries.into_iter()
+    }
+
+    /// Collects every value in `node`'s subtree, in root-to-leaf,
+    /// children-sorted (exact-before-wildcard) order
+    fn collect_subtree<'node>(node: &'node Node<E, W, V>, values: &mut Vec<&'node V>) {
+        if let Some(value) = &node.value {
+            values.push(value);
+        }
+
+        if let Some(children) = &node.children {
+            for child in children {
+                Self::collect_subtree(child, values);
+            }
+        }
+    }
+
+    /// Collects every `(key, value)` pair in `node`'s subtree, `prefix` being
+    /// the key parts already accumulated on the path down to `node`
+    fn collect_entries<'node>(
+        node: &'node Node<E, W, V>,
+        mut prefix: Vec<KeyPart<E, W>>,
+        entries: &mut Vec<(Vec<KeyPart<E, W>>, &'node V)>,
+    ) where
+        E: Clone,
+        W: Clone,
+    {
+        prefix.extend(node.key_parts.iter().cloned());
+
+        if let Some(value) = &node.value {
+            entries.push((prefix.clone(), value));
+        }
+
+        if let Some(children) = &node.children {
+            for child in children {
+                Self::collect_entries(child, prefix.clone(), entries);
+            }
+        }
+    }
+
+    /// Returns the value associated with `key`, like [`get`](Self::get), but
+    /// fails if `key` is ambiguous
+    ///
+    /// Only relevant when the tree has wildcards: a key can then match more
+    /// than one stored pattern (e.g. `a/*` and `*/b` both match `a/b`).
+    /// Unlike `get`, which silently returns whichever match it finds first,
+    /// this continues the wildcard traversal (bounded by the tree's maximum
+    /// wildcard depth) and fails as soon as a second match is found.
+    pub fn get_unique(&self, key: impl IntoIterator<Item = E>) -> Result<Option<&V>, AmbiguousKey> {
+        let key = key.into_iter().collect::<Vec<_>>();
+        let mut found = None;
+
+        Self::visit_unique(&self.root, &key, 0, self.max_wildcard_depth, &mut found)?;
+
+        Ok(found)
+    }
+
+    /// Walks every path through `node` consistent with `key`, failing as
+    /// soon as a second value-bearing terminal matching the full `key` is found
+    fn visit_unique<'node>(
+        node: &'node Node<E, W, V>,
+        key: &[E],
+        key_pos: usize,
+        wildcard_budget: usize,
+        found: &mut Option<&'node V>,
+    ) -> Result<(), AmbiguousKey> {
+        let Some((key_pos, wildcard_budget)) = Self::consume_edge(node, key, key_pos, wildcard_budget) else {
+            return Ok(());
+        };
+
+        if key_pos == key.len() {
+            if let Some(value) = &node.value {
+                if found.is_some() {
+                    return Err(AmbiguousKey);
+                }
+                *found = Some(value);
+            }
+            return Ok(());
+        }
+
+        let Some(children) = &node.children else {
+            return Ok(());
+        };
+
+        for child in children {
+            if matches!(child.key_parts.first(), Some(KeyPart::Exact(e)) if Some(e) == key.get(key_pos))
+            {
+                Self::visit_unique(child, key, key_pos, wildcard_budget, found)?;
+            }
+        }
+        for child in children {
+            if matches!(child.key_parts.first(), Some(KeyPart::Wildcard(_))) {
+                Self::visit_unique(child, key, key_pos, wildcard_budget, found)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AmbiguousKey;
+    use crate::{builder::PrefixTreeMapBuilder, key_part::KeyPart};
+
+    #[test]
+    fn find_postfixes_collects_every_value_under_a_prefix() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a"], 1);
+        builder.insert_exact(["a", "b"], 2);
+        builder.insert_exact(["a", "c"], 3);
+        builder.insert_exact(["z"], 4);
+        let map = builder.build();
+
+        let mut postfixes = map.find_postfixes(["a"]);
+        postfixes.sort();
+        assert_eq!(postfixes, vec![&1, &2, &3]);
+    }
+
+    #[test]
+    fn find_postfixes_returns_empty_when_prefix_is_not_in_the_tree() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a"], 1);
+        let map = builder.build();
+
+        assert_eq!(map.find_postfixes(["b"]), Vec::<&i32>::new());
+    }
+
+    #[test]
+    fn find_postfixes_follows_both_an_exact_and_an_overlapping_wildcard_child() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a", "x"], 1);
+        builder.insert([KeyPart::Wildcard("w"), KeyPart::Exact("y")], 2);
+        let map = builder.build();
+
+        let mut postfixes = map.find_postfixes(["a"]);
+        postfixes.sort();
+        assert_eq!(postfixes, vec![&1, &2]);
+    }
+
+    #[test]
+    fn get_unique_returns_the_value_for_an_unambiguous_match() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a", "b"], 1);
+        let map = builder.build();
+
+        assert_eq!(map.get_unique(["a", "b"]), Ok(Some(&1)));
+    }
+
+    #[test]
+    fn get_unique_returns_none_when_nothing_matches() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a"], 1);
+        let map = builder.build();
+
+        assert_eq!(map.get_unique(["b"]), Ok(None));
+    }
+
+    #[test]
+    fn get_unique_fails_when_two_wildcard_patterns_both_match() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert([KeyPart::Exact("a"), KeyPart::Wildcard("w")], 1);
+        builder.insert([KeyPart::Wildcard("w"), KeyPart::Exact("b")], 2);
+        let map = builder.build();
+
+        assert_eq!(map.get_unique(["a", "b"]), Err(AmbiguousKey));
+    }
+
+    #[test]
+    fn iter_reconstructs_every_key_including_compacted_runs() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a", "b"], 1);
+        builder.insert([KeyPart::Wildcard("w")], 2);
+        let map = builder.build();
+
+        let mut entries = map.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(_key, value)| **value);
+
+        assert_eq!(
+            entries,
+            vec![
+                (vec![KeyPart::Exact("a"), KeyPart::Exact("b")], &1),
+                (vec![KeyPart::Wildcard("w")], &2),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_prefixes_collects_every_value_along_the_path() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert_exact(["a"], 1);
+        builder.insert_exact(["a", "b"], 2);
+        let map = builder.build();
+
+        assert_eq!(map.find_prefixes(["a", "b"]), vec![&1, &2]);
+    }
+
+    #[test]
+    fn find_longest_prefix_picks_the_exact_match_over_an_equally_deep_wildcard_one() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, i32>::new();
+        builder.insert([KeyPart::Wildcard("a")], 1);
+        builder.insert_exact(["x"], 2);
+        let map = builder.build();
+
+        assert_eq!(map.find_longest_prefix(["x"]), Some(&2));
+    }
+
+    #[test]
+    fn find_longest_prefix_picks_the_deepest_match_regardless_of_sibling_visitation_order() {
+        let mut builder = PrefixTreeMapBuilder::<&str, &str, &str>::new();
+        // Sorts before the `"b"` branch below, so it is visited (and pushed)
+        // first: taking the last pushed value would pick the shallow match.
+        builder.insert(
+            [
+                KeyPart::Wildcard("a"),
+                KeyPart::Exact("y"),
+                KeyPart::Exact("z"),
+            ],
+            "deep",
+        );
+        builder.insert([KeyPart::Wildcard("b"), KeyPart::Wildcard("bb")], "shallow");
+        let map = builder.build();
+
+        assert_eq!(map.find_longest_prefix(["x", "y", "z"]), Some(&"deep"));
+    }
+}
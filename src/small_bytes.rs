@@ -0,0 +1,137 @@
+//! A small-buffer-optimized byte string, for use as a [`KeyPart`](crate::KeyPart) element
+//!
+//! Short segments are stored inline, avoiding a heap allocation per key part
+//! for the common case of byte- or `str`-oriented keys; longer segments fall
+//! back to a heap allocation shared via [`Arc`], so cloning never copies the
+//! underlying bytes once a segment no longer fits inline.
+
+use {crate::frozen::ToBytes, core::cmp::Ordering, std::sync::Arc};
+
+/// The number of bytes a [`SmallBytes`] can store inline, before falling
+/// back to a heap allocation
+const INLINE_CAP: usize = core::mem::size_of::<*const u8>();
+
+/// A small-buffer-optimized, immutable byte string
+#[derive(Clone, Debug)]
+pub enum SmallBytes {
+    /// A segment of at most [`INLINE_CAP`] bytes, stored inline
+    Inline {
+        /// The number of bytes actually in use
+        len: u8,
+        /// The inline storage; only the first `len` bytes are meaningful
+        bytes: [u8; INLINE_CAP],
+    },
+
+    /// A longer segment, heap-allocated and reference-counted
+    Heap(Arc<[u8]>),
+}
+
+impl SmallBytes {
+    /// Creates a new `SmallBytes` from `bytes`, storing it inline if it's short enough
+    pub fn new(bytes: &[u8]) -> Self {
+        if bytes.len() > INLINE_CAP {
+            return Self::Heap(Arc::from(bytes));
+        }
+
+        let mut inline = [0; INLINE_CAP];
+        inline[..bytes.len()].copy_from_slice(bytes);
+
+        Self::Inline {
+            len: bytes.len() as u8,
+            bytes: inline,
+        }
+    }
+
+    /// Returns the bytes of this segment
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Inline { len, bytes } => &bytes[..usize::from(*len)],
+            Self::Heap(bytes) => bytes,
+        }
+    }
+}
+
+impl From<&[u8]> for SmallBytes {
+    fn from(bytes: &[u8]) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<&str> for SmallBytes {
+    fn from(s: &str) -> Self {
+        Self::new(s.as_bytes())
+    }
+}
+
+impl PartialEq for SmallBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for SmallBytes {}
+
+impl PartialOrd for SmallBytes {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmallBytes {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl ToBytes for SmallBytes {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self::new(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_a_segment_at_the_inline_boundary_inline() {
+        let bytes = vec![1; INLINE_CAP];
+        let small = SmallBytes::new(&bytes);
+
+        assert!(matches!(small, SmallBytes::Inline { .. }));
+        assert_eq!(small.as_bytes(), bytes.as_slice());
+    }
+
+    #[test]
+    fn falls_back_to_heap_storage_past_the_inline_boundary() {
+        let bytes = vec![1; INLINE_CAP + 1];
+        let small = SmallBytes::new(&bytes);
+
+        assert!(matches!(small, SmallBytes::Heap(_)));
+        assert_eq!(small.as_bytes(), bytes.as_slice());
+    }
+
+    #[test]
+    fn equality_and_ordering_compare_the_underlying_bytes_regardless_of_storage() {
+        let inline = SmallBytes::new(&[1]);
+        let heap = SmallBytes::new(&[1; INLINE_CAP + 1]);
+
+        assert_eq!(SmallBytes::new(&[1]), inline);
+        assert_ne!(inline, heap);
+        assert!(inline < heap);
+    }
+
+    #[test]
+    fn round_trips_through_to_bytes_and_from_bytes() {
+        let small = SmallBytes::new(b"hello");
+
+        let mut out = Vec::new();
+        small.to_bytes(&mut out);
+
+        assert_eq!(SmallBytes::from_bytes(&out), small);
+    }
+}
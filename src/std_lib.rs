@@ -0,0 +1,6 @@
+//! Re-exports of standard library types used throughout the crate
+//!
+//! Centralizing these makes it easier to swap implementations later
+//! without touching every call site.
+
+pub use std::{cmp::Ordering, collections::BinaryHeap};